@@ -2,13 +2,22 @@ import * as fs from "node:fs";
 import * as os from "node:os";
 import * as path from "node:path";
 import { Command } from "commander";
-import { MODEL_IDS, PATHS } from "../config";
+import {
+  CONFIG,
+  MAX_WORKER_MEMORY_MB,
+  MODEL_IDS,
+  PATHS,
+  PROVIDERS,
+  WORKER_BOOT_TIMEOUT_MS,
+  WORKER_TIMEOUT_MS,
+} from "../config";
 import { gracefulExit } from "../lib/utils/exit";
 import { findProjectRoot } from "../lib/utils/project-root";
 
 export const doctor = new Command("doctor")
   .description("Check osgrep health and paths")
-  .action(async () => {
+  .option("--config", "Print the resolved runtime configuration as JSON")
+  .action(async (options: { config?: boolean }) => {
     console.log("🏥 osgrep Doctor\n");
 
     const root = PATHS.globalRoot;
@@ -56,6 +65,24 @@ export const doctor = new Command("doctor")
     console.log(
       `\nSystem: ${os.platform()} ${os.arch()} | Node: ${process.version}`,
     );
+
+    if (options.config) {
+      // Loaded lazily so plain `doctor` doesn't pull in the ONNX runtime.
+      const { describeConfig } = await import("../lib/workers/orchestrator");
+      // Everything a bug report needs to reproduce model/worker behaviour.
+      const resolved = {
+        modelIds: MODEL_IDS,
+        providers: PROVIDERS,
+        config: CONFIG,
+        workerTimeoutMs: WORKER_TIMEOUT_MS,
+        workerBootTimeoutMs: WORKER_BOOT_TIMEOUT_MS,
+        maxWorkerMemoryMb: MAX_WORKER_MEMORY_MB,
+        paths: PATHS,
+        workers: describeConfig(),
+      };
+      console.log(`\nConfiguration:\n${JSON.stringify(resolved, null, 2)}`);
+    }
+
     console.log("\nIf you see ✅ everywhere, you are ready to search!");
 
     await gracefulExit();
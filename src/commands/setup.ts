@@ -1,7 +1,7 @@
 import * as fs from "node:fs";
 import * as path from "node:path";
 import { Command } from "commander";
-import { MODEL_IDS, PATHS } from "../config";
+import { HF_ENDPOINT, MODEL_IDS, PATHS } from "../config";
 import { ensureGrammars } from "../lib/index/grammar-loader";
 import { ensureSetup } from "../lib/setup/setup-helpers";
 import { gracefulExit } from "../lib/utils/exit";
@@ -58,7 +58,7 @@ export const setup = new Command("setup")
     } else {
       console.log(`⚠ Skiplist missing, attempting to download...`);
       try {
-        const url = `https://huggingface.co/${MODEL_IDS.colbert}/resolve/main/skiplist.json`;
+        const url = `${HF_ENDPOINT}/${MODEL_IDS.colbert}/resolve/main/skiplist.json`;
         const response = await fetch(url);
         if (response.ok) {
           const buffer = await response.arrayBuffer();
@@ -6,6 +6,11 @@ export const MODEL_IDS = {
   colbert: "ryandono/mxbai-edge-colbert-v0-17m-onnx-int8",
 };
 
+// HuggingFace endpoint used for model downloads (set HF_ENDPOINT for a mirror)
+export const HF_ENDPOINT = (
+  process.env.HF_ENDPOINT || "https://huggingface.co"
+).replace(/\/+$/, "");
+
 // Provider types
 export type EmbedProvider = "local" | "qwen";
 export type RerankProvider = "local" | "zeroentropy";
@@ -45,14 +50,32 @@ const DEFAULT_WORKER_THREADS = (() => {
   return Math.max(1, Math.min(HARD_CAP, isCloud ? 14 : cores));
 })();
 
+// Local dense vectors can be truncated (Matryoshka-style) below the model's
+// hidden size; they are re-normalized after truncation.
+const LOCAL_VECTOR_DIM = (() => {
+  const fromEnv = Number.parseInt(process.env.OSGREP_VECTOR_DIM ?? "", 10);
+  return Number.isFinite(fromEnv) && fromEnv > 0 ? fromEnv : 384;
+})();
+
+const DEFAULT_ONNX_THREADS = (() => {
+  const fromEnv = Number.parseInt(process.env.OSGREP_ONNX_THREADS ?? "", 10);
+  return Number.isFinite(fromEnv) && fromEnv > 0 ? fromEnv : 1;
+})();
+
 export const CONFIG = {
-  VECTOR_DIM: PROVIDERS.embed === "qwen" ? 4096 : 384,
+  VECTOR_DIM: PROVIDERS.embed === "qwen" ? 4096 : LOCAL_VECTOR_DIM,
   COLBERT_DIM: 48,
   MAX_CHUNK_CHARS: 2000,
   MAX_CHUNK_LINES: 75,
   EMBED_BATCH_SIZE: 24,
   WORKER_THREADS: DEFAULT_WORKER_THREADS,
-  QUERY_PREFIX: "",
+  // Intra- and inter-op threads per ONNX session (each worker owns its own sessions)
+  ONNX_THREADS: DEFAULT_ONNX_THREADS,
+  // Prepended before local dense tokenization only (e.g. "query: " / "passage: " for e5)
+  QUERY_PREFIX: process.env.OSGREP_DENSE_QUERY_PREFIX || "",
+  DOC_PREFIX: process.env.OSGREP_DENSE_DOC_PREFIX || "",
+  // Instruction template applied to search queries, e.g. "query: {query}"
+  QUERY_TEMPLATE: process.env.OSGREP_QUERY_TEMPLATE || "",
 };
 
 export const WORKER_TIMEOUT_MS = Number.parseInt(
@@ -18,6 +18,7 @@ export type EvalResult = {
   rr: number;
   found: boolean;
   recall: number;
+  ndcg: number;
   path: string;
   query: string;
   note?: string;
@@ -91,7 +92,7 @@ export const cases: EvalCase[] = [
   {
     query: "How do we mean-pool Granite outputs to 384 dimensions?",
     expectedPath: "src/lib/workers/embeddings/granite.ts",
-    note: "meanPool normalizes and pads vectors to CONFIG.VECTOR_DIM.",
+    note: "GraniteModel.pool truncates and normalizes vectors to CONFIG.VECTOR_DIM.",
   },
   {
     query: "How does ColBERT quantize token grids to int8 with a scale?",
@@ -544,6 +545,39 @@ export const cases: EvalCase[] = [
 
 const topK = 20;
 
+/**
+ * Fraction of `relevant` indices that appear in the first `k` of `predicted`.
+ */
+export function recallAtK(
+  predicted: number[],
+  relevant: number[],
+  k: number,
+): number {
+  if (relevant.length === 0) return 0;
+  const top = new Set(predicted.slice(0, k));
+  const hits = relevant.filter((idx) => top.has(idx)).length;
+  return hits / relevant.length;
+}
+
+/**
+ * Normalized DCG over the first `k` of `predicted`, where `relevance[idx]`
+ * is the graded relevance of document `idx` (missing entries count as 0).
+ */
+export function ndcgAtK(
+  predicted: number[],
+  relevance: number[],
+  k: number,
+): number {
+  const dcg = (gains: number[]) =>
+    gains
+      .slice(0, k)
+      .reduce((sum, gain, i) => sum + (2 ** gain - 1) / Math.log2(i + 2), 0);
+
+  const ideal = dcg([...relevance].sort((a, b) => b - a));
+  if (ideal === 0) return 0;
+  return dcg(predicted.map((idx) => relevance[idx] ?? 0)) / ideal;
+}
+
 export function evaluateCase(
   response: SearchResponse,
   evalCase: EvalCase,
@@ -569,12 +603,16 @@ export function evaluateCase(
     evalCase.avoidPath && avoidRank >= 0 && (rank === -1 || avoidRank < rank);
   const found = rank >= 0 && !hitAvoid;
   const rr = found ? 1 / (rank + 1) : 0;
-  const recall = found && rank < 10 ? 1 : 0;
+  const ranked = response.data.map((_, i) => i);
+  const recall = found ? recallAtK(ranked, [rank], 10) : 0;
+  const relevance = ranked.map((i) => (found && i === rank ? 1 : 0));
+  const ndcg = ndcgAtK(ranked, relevance, 10);
 
   return {
     rr,
     found,
     recall,
+    ndcg,
     path: evalCase.expectedPath,
     query: evalCase.query,
     note: evalCase.note,
@@ -631,6 +669,8 @@ async function run() {
   const mrr = results.reduce((sum, r) => sum + r.rr, 0) / results.length;
   const recallAt10 =
     results.reduce((sum, r) => sum + r.recall, 0) / results.length;
+  const ndcgAt10 =
+    results.reduce((sum, r) => sum + r.ndcg, 0) / results.length;
   const avgTime =
     results.reduce((sum, r) => sum + r.timeMs, 0) / results.length;
 
@@ -651,6 +691,7 @@ async function run() {
   console.log("=".repeat(80));
   console.log(`MRR: ${mrr.toFixed(3)}`);
   console.log(`Recall@10: ${recallAt10.toFixed(3)}`);
+  console.log(`nDCG@10: ${ndcgAt10.toFixed(3)}`);
   console.log(`Avg query time: ${avgTime.toFixed(0)}ms`);
   console.log(`Total time: ${totalTime.toFixed(0)}ms`);
   console.log(
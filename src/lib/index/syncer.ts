@@ -96,9 +96,13 @@ export async function initialSync(
     }
 
     if (!dryRun) {
+      // Drop before hasAnyRows: it validates the schema, which an index built
+      // for another vector width fails, and reset is how users recover.
+      if (reset) await vectorDb.drop();
       const hasRows = await vectorDb.hasAnyRows();
       const hasMeta = (await metaCache.getAllKeys()).size > 0;
-      const isInconsistent = (hasRows && !hasMeta) || (!hasRows && hasMeta);
+      const isInconsistent =
+        !reset && ((hasRows && !hasMeta) || (!hasRows && hasMeta));
 
       if (reset || isInconsistent) {
         if (isInconsistent) {
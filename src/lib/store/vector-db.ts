@@ -81,6 +81,16 @@ export class VectorDB {
         )}). Please run "osgrep index --reset" to rebuild the index.`,
       );
     }
+
+    // A provider or OSGREP_VECTOR_DIM change leaves vectors of the old width.
+    const vectorField = schema.fields.find((f) => f.name === "vector");
+    const storedDim = (vectorField?.type as { listSize?: number } | undefined)
+      ?.listSize;
+    if (storedDim !== undefined && storedDim !== CONFIG.VECTOR_DIM) {
+      throw new Error(
+        `[vector-db] index vectors have ${storedDim} dimensions but ${CONFIG.VECTOR_DIM} are configured. Please run "osgrep index --reset" to rebuild the index.`,
+      );
+    }
   }
 
   private buildSchema(): Schema {
@@ -142,18 +152,25 @@ export class VectorDB {
 
   async ensureTable(): Promise<lancedb.Table> {
     const db = await this.getDb();
+    let existing: lancedb.Table | null = null;
     try {
-      const table = await db.openTable(TABLE_NAME);
-      await this.validateSchema(table);
-      return table;
+      existing = await db.openTable(TABLE_NAME);
     } catch (_err) {
-      const schema = this.buildSchema();
-      const table = await db.createTable(TABLE_NAME, [this.seedRow()], {
-        schema,
-      });
-      await table.delete('id = "seed"');
-      return table;
+      // No table yet; create it below.
+    }
+    if (existing) {
+      // Outside the try so schema drift reaches the user instead of
+      // falling through to createTable.
+      await this.validateSchema(existing);
+      return existing;
     }
+
+    const schema = this.buildSchema();
+    const table = await db.createTable(TABLE_NAME, [this.seedRow()], {
+      schema,
+    });
+    await table.delete('id = "seed"');
+    return table;
   }
 
   async insertBatch(records: VectorRecord[]): Promise<void> {
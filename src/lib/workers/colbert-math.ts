@@ -29,21 +29,34 @@ function loadSkipIds(): Set<number> {
 
   if (fs.existsSync(skipPath)) {
     try {
-      const parsed = JSON.parse(fs.readFileSync(skipPath, "utf8")) as number[];
+      const parsed = JSON.parse(fs.readFileSync(skipPath, "utf8"));
+      if (!Array.isArray(parsed)) {
+        throw new Error("expected an array of token ids");
+      }
       SKIP_IDS = new Set<number>(parsed.map((n) => Number(n)));
       return SKIP_IDS;
-    } catch (_e) {
-      // fall through to empty set
+    } catch (e) {
+      // The skiplist is optional; a bad one shouldn't break reranking.
+      console.warn(
+        `[osgrep] Ignoring malformed skiplist at ${skipPath}: ${
+          e instanceof Error ? e.message : String(e)
+        }`,
+      );
     }
   }
   SKIP_IDS = new Set<number>();
   return SKIP_IDS;
 }
 
+export function skipListSize(): number {
+  return loadSkipIds().size;
+}
+
 export function maxSim(
   queryEmbeddings: number[][] | Float32Array[],
   docEmbeddings: number[][] | Float32Array[],
   docTokenIds?: number[],
+  extraSkipIds?: Set<number>,
 ): number {
   if (queryEmbeddings.length === 0 || docEmbeddings.length === 0) {
     return 0;
@@ -64,7 +77,11 @@ export function maxSim(
     let maxDotProduct = -Infinity;
     for (let idx = 0; idx < dVecs.length; idx++) {
       const tokenId = dTokenIds ? dTokenIds[idx] : null;
-      if (tokenId !== null && skipIds.has(Number(tokenId))) continue;
+      if (
+        tokenId !== null &&
+        (skipIds.has(Number(tokenId)) || extraSkipIds?.has(Number(tokenId)))
+      )
+        continue;
       const dVec = dVecs[idx];
       const dim = Math.min(qVec.length, dVec.length);
       const dot = inner(qVec.subarray(0, dim), dVec.subarray(0, dim));
@@ -6,11 +6,38 @@ import {
 const QUERY_MARKER_TOKEN = "[Q] ";
 const DOC_MARKER_TOKEN = "[D] ";
 const MASK_TOKEN = "[MASK]";
-const QUERY_MAXLEN = 32; // Standard ColBERT query length
-const DOC_MAXLEN = 512; // Standard ColBERT document length
+const readMaxLen = (name: string, fallback: number): number => {
+  const fromEnv = Number.parseInt(process.env[name] ?? "", 10);
+  return Number.isFinite(fromEnv) ? fromEnv : fallback;
+};
+// Defaults are the standard ColBERT query/document lengths. Longer lengths keep
+// more context, but MaxSim cost grows with query tokens × doc tokens.
+const QUERY_MAXLEN = readMaxLen("OSGREP_COLBERT_QUERY_MAXLEN", 32);
+const DOC_MAXLEN = readMaxLen("OSGREP_COLBERT_DOC_MAXLEN", 512);
+// [CLS] + marker + [SEP] + at least one content token
+const MIN_MAXLEN = 4;
+// Pad queries to the next multiple of 8 instead of always to the query max length.
+const DYNAMIC_QUERY_LEN =
+  process.env.OSGREP_COLBERT_DYNAMIC_QUERY === "1" ||
+  process.env.OSGREP_COLBERT_DYNAMIC_QUERY === "true";
+// Which end of an over-long document survives truncation ("head" keeps the start).
+const TRUNCATION_SIDE =
+  process.env.OSGREP_COLBERT_TRUNCATION === "tail" ? "tail" : "head";
+
+// Lengths are as configured; init() may still clamp them to the model limit.
+export function describeConfig() {
+  return {
+    queryMaxLen: QUERY_MAXLEN,
+    docMaxLen: DOC_MAXLEN,
+    dynamicQueryLen: DYNAMIC_QUERY_LEN,
+    truncationSide: TRUNCATION_SIDE,
+  };
+}
 
 export class ColBERTTokenizer {
   private tokenizer: PreTrainedTokenizer | null = null;
+  private queryMaxLen = QUERY_MAXLEN;
+  private docMaxLen = DOC_MAXLEN;
   private specialTokenIds: {
     cls: number;
     sep: number;
@@ -21,8 +48,31 @@ export class ColBERTTokenizer {
   } | null = null;
 
   async init(modelPath: string) {
+    if (QUERY_MAXLEN < MIN_MAXLEN || DOC_MAXLEN < MIN_MAXLEN) {
+      throw new Error(
+        `ColBERT max lengths must be at least ${MIN_MAXLEN} to fit [CLS], the marker and [SEP] (got query=${QUERY_MAXLEN}, doc=${DOC_MAXLEN})`,
+      );
+    }
+
     this.tokenizer = await AutoTokenizer.from_pretrained(modelPath);
 
+    // Clamp both lengths to the model's position limit.
+    const modelMax = this.tokenizer.model_max_length;
+    if (Number.isFinite(modelMax) && modelMax > 0) {
+      const envNames = {
+        queryMaxLen: "OSGREP_COLBERT_QUERY_MAXLEN",
+        docMaxLen: "OSGREP_COLBERT_DOC_MAXLEN",
+      } as const;
+      for (const key of ["queryMaxLen", "docMaxLen"] as const) {
+        if (this[key] > modelMax) {
+          console.warn(
+            `[osgrep] ColBERT ${envNames[key]} ${this[key]} exceeds the model limit; using ${modelMax}`,
+          );
+          this[key] = modelMax;
+        }
+      }
+    }
+
     // Get special token IDs with fallbacks
     // We use the IDs we discovered in validation: [Q]=50368, [D]=50369
     // But we still try to look them up dynamically first.
@@ -35,12 +85,31 @@ export class ColBERTTokenizer {
       sep_token: string;
       pad_token: string;
     }>;
-    const clsId = get(specialTokens.cls_token ?? "[CLS]") ?? 50281;
-    const sepId = get(specialTokens.sep_token ?? "[SEP]") ?? 50282;
-    const padId = get(specialTokens.pad_token ?? "[PAD]") ?? 50283;
-    const maskId = get(MASK_TOKEN) ?? 50284;
-    const queryMarkerId = get(QUERY_MARKER_TOKEN) ?? 50368;
-    const docMarkerId = get(DOC_MARKER_TOKEN) ?? 50369;
+    // [CLS]/[SEP] frame every sequence; guessing their ids silently corrupts
+    // every embedding, so refuse to run without them.
+    const clsToken = specialTokens.cls_token ?? "[CLS]";
+    const sepToken = specialTokens.sep_token ?? "[SEP]";
+    const clsId = get(clsToken);
+    const sepId = get(sepToken);
+    if (clsId === undefined || sepId === undefined) {
+      const missing = [clsToken, sepToken].filter((t) => get(t) === undefined);
+      throw new Error(
+        `ColBERT tokenizer at ${modelPath} is missing ${missing.join(", ")}`,
+      );
+    }
+
+    const withFallback = (token: string, fallback: number): number => {
+      const id = get(token);
+      if (id !== undefined) return id;
+      console.warn(
+        `[osgrep] ColBERT tokenizer at ${modelPath} is missing ${token.trim()}; using default id ${fallback}, embeddings may be wrong.`,
+      );
+      return fallback;
+    };
+    const padId = withFallback(specialTokens.pad_token ?? "[PAD]", 50283);
+    const maskId = withFallback(MASK_TOKEN, 50284);
+    const queryMarkerId = withFallback(QUERY_MARKER_TOKEN, 50368);
+    const docMarkerId = withFallback(DOC_MARKER_TOKEN, 50369);
 
     this.specialTokenIds = {
       cls: clsId,
@@ -52,6 +121,22 @@ export class ColBERTTokenizer {
     };
   }
 
+  /** Ids of the structural tokens ([CLS], [SEP], [PAD], [Q], [D]). */
+  getSpecialTokenIds(): number[] {
+    if (!this.specialTokenIds) {
+      throw new Error("Tokenizer not initialized. Call init() first.");
+    }
+    const { cls, sep, pad, queryMarker, docMarker } = this.specialTokenIds;
+    return [cls, sep, pad, queryMarker, docMarker];
+  }
+
+  getPadId(): number {
+    if (!this.specialTokenIds) {
+      throw new Error("Tokenizer not initialized. Call init() first.");
+    }
+    return this.specialTokenIds.pad;
+  }
+
   async encodeQuery(
     text: string,
   ): Promise<{ input_ids: bigint[]; attention_mask: bigint[] }> {
@@ -63,7 +148,7 @@ export class ColBERTTokenizer {
     const encoded = await this.tokenizer(text, {
       add_special_tokens: false,
       truncation: true,
-      max_length: QUERY_MAXLEN - 2, // Reserve space for [CLS] and [Q]
+      max_length: this.queryMaxLen - 2, // Reserve space for [CLS] and [Q]
     });
 
     const { input_ids } = encoded;
@@ -76,14 +161,17 @@ export class ColBERTTokenizer {
       this.specialTokenIds.sep,
     ];
 
-    // Query Expansion: pad with [MASK] tokens up to QUERY_MAXLEN
-    while (finalIds.length < QUERY_MAXLEN) {
+    // Query Expansion: pad with [MASK] tokens up to the target length
+    const targetLen = DYNAMIC_QUERY_LEN
+      ? Math.min(this.queryMaxLen, Math.ceil((finalIds.length + 1) / 8) * 8)
+      : this.queryMaxLen;
+    while (finalIds.length < targetLen) {
       finalIds.push(this.specialTokenIds.mask);
     }
 
     // Truncate if somehow longer (safety check)
-    if (finalIds.length > QUERY_MAXLEN) {
-      finalIds.length = QUERY_MAXLEN;
+    if (finalIds.length > this.queryMaxLen) {
+      finalIds.length = this.queryMaxLen;
     }
 
     // Create attention mask (1 for all tokens, since MASK is also attended to)
@@ -102,20 +190,28 @@ export class ColBERTTokenizer {
       throw new Error("Tokenizer not initialized. Call init() first.");
     }
 
-    // Tokenize without special tokens
+    // Tokenize without special tokens; truncate ourselves so either end can be kept
     const encoded = await this.tokenizer(text, {
       add_special_tokens: false,
-      truncation: true,
-      max_length: DOC_MAXLEN - 3, // Reserve space for [CLS], [D], and [SEP]
+      truncation: false,
     });
 
-    const { input_ids } = encoded;
+    const maxContent = this.docMaxLen - 3; // Reserve space for [CLS], [D], and [SEP]
+    const allIds = Array.from(encoded.input_ids.data as BigInt64Array).map(
+      Number,
+    );
+    const contentIds =
+      allIds.length <= maxContent
+        ? allIds
+        : TRUNCATION_SIDE === "tail"
+          ? allIds.slice(allIds.length - maxContent)
+          : allIds.slice(0, maxContent);
 
     // Build sequence: [CLS] [D] token1 token2 ... [SEP]
     const finalIds: number[] = [
       this.specialTokenIds.cls,
       this.specialTokenIds.docMarker,
-      ...Array.from(input_ids.data as BigInt64Array).map(Number),
+      ...contentIds,
       this.specialTokenIds.sep,
     ];
 
@@ -3,7 +3,7 @@ import * as os from "node:os";
 import * as path from "node:path";
 import { parentPort } from "node:worker_threads";
 import { env, pipeline } from "@huggingface/transformers";
-import { MODEL_IDS } from "../../config";
+import { HF_ENDPOINT, MODEL_IDS } from "../../config";
 
 // Configuration
 const HOMEDIR = os.homedir();
@@ -11,6 +11,7 @@ const CACHE_DIR = path.join(HOMEDIR, ".osgrep", "models");
 env.cacheDir = CACHE_DIR;
 env.allowLocalModels = true;
 env.allowRemoteModels = true;
+env.remoteHost = `${HF_ENDPOINT}/`;
 
 // Suppress noisy warnings from transformers.js/onnxruntime
 const originalWarn = console.warn;
@@ -66,7 +67,7 @@ async function downloadModelWithTimeout(modelId: string, dtype: PipelineDType) {
 
 // Helper to manually download extra files like skiplist.json
 async function downloadExtraFile(modelId: string, filename: string) {
-  const url = `https://huggingface.co/${modelId}/resolve/main/${filename}`;
+  const url = `${HF_ENDPOINT}/${modelId}/resolve/main/${filename}`;
   // Construct path: ~/.osgrep/models/ryandono/osgrep-colbert-q8/skiplist.json
   const destDir = path.join(CACHE_DIR, ...modelId.split("/"));
   const destPath = path.join(destDir, filename);
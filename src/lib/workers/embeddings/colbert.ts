@@ -3,9 +3,9 @@ import * as path from "node:path";
 import * as ort from "onnxruntime-node";
 import { MODEL_IDS, PATHS } from "../../../config";
 import { ColBERTTokenizer } from "../colbert-tokenizer";
+import { createOnnxSession, withPositionIds } from "../onnx-session";
 
 const CACHE_DIR = PATHS.models;
-const ONNX_THREADS = 1;
 const LOG_MODELS =
   process.env.OSGREP_DEBUG_MODELS === "1" ||
   process.env.OSGREP_DEBUG_MODELS === "true";
@@ -40,19 +40,15 @@ export class ColbertModel {
 
     await this.tokenizer.init(basePath);
 
-    const sessionOptions: ort.InferenceSession.SessionOptions = {
-      executionProviders: ["cpu"],
-      intraOpNumThreads: ONNX_THREADS,
-      interOpNumThreads: 1,
-      graphOptimizationLevel: "all",
-    };
-
     log(`Worker: Loading ColBERT ONNX session from ${modelPath}`);
-    this.session = await ort.InferenceSession.create(modelPath, sessionOptions);
+    this.session = await createOnnxSession(modelPath, "ColBERT");
 
     if (!this.session) {
       throw new Error(`ColBERT ONNX load failed; tried ${modelPath}`);
     }
+    log(
+      `Worker: ColBERT inputs [${this.session.inputNames.join(", ")}], outputs [${this.session.outputNames.join(", ")}]`,
+    );
   }
 
   isReady(): boolean {
@@ -64,7 +60,7 @@ export class ColbertModel {
     denseVectors: Float32Array[],
     vectorDimensions: number,
   ): Promise<HybridResult[]> {
-    if (!this.session || !this.tokenizer) return [];
+    if (!this.session || !this.tokenizer || texts.length === 0) return [];
     const tokenizer = this.tokenizer;
     const session = this.session;
 
@@ -75,7 +71,7 @@ export class ColbertModel {
     const maxLen = Math.max(...encodedBatch.map((e) => e.input_ids.length));
     const batchInputIds = new BigInt64Array(texts.length * maxLen);
     const batchAttentionMask = new BigInt64Array(texts.length * maxLen);
-    const padId = BigInt(50283);
+    const padId = BigInt(tokenizer.getPadId());
 
     for (let i = 0; i < encodedBatch.length; i++) {
       const encoded = encodedBatch[i];
@@ -99,7 +95,7 @@ export class ColbertModel {
       ]),
     };
 
-    const sessionOut = await session.run(feeds);
+    const sessionOut = await session.run(withPositionIds(session, feeds));
     const outputName = session.outputNames[0];
     const output = sessionOut[outputName];
     if (!output) {
@@ -189,7 +185,7 @@ export class ColbertModel {
     feeds: Record<string, ort.Tensor>,
   ): Promise<ort.InferenceSession.OnnxValueMapType> {
     if (!this.session) throw new Error("ColBERT session not initialized");
-    return this.session.run(feeds);
+    return this.session.run(withPositionIds(this.session, feeds));
   }
 
   getOutputName(): string {
@@ -6,9 +6,23 @@ import {
 } from "@huggingface/transformers";
 import * as ort from "onnxruntime-node";
 import { CONFIG, MODEL_IDS, PATHS } from "../../../config";
+import { createOnnxSession, withPositionIds } from "../onnx-session";
 
 const CACHE_DIR = PATHS.models;
-const ONNX_THREADS = 1;
+const DEFAULT_MAX_LENGTH = 256;
+const MAX_LENGTH_CEILING = 512;
+// HF writes a huge sentinel (e.g. 1e30) when the model author left it unset.
+const MAX_LENGTH_SENTINEL = 1e6;
+// Explicit override for dense truncation; wins over tokenizer_config.json.
+const ENV_MAX_LENGTH = Number.parseInt(
+  process.env.OSGREP_DENSE_MAXLEN ?? "",
+  10,
+);
+// How token states become one vector: masked mean (default), [CLS], or max.
+type DensePooling = "mean" | "cls" | "max";
+const ENV_POOLING = process.env.OSGREP_DENSE_POOLING;
+const POOLING: DensePooling =
+  ENV_POOLING === "cls" || ENV_POOLING === "max" ? ENV_POOLING : "mean";
 const LOG_MODELS =
   process.env.OSGREP_DEBUG_MODELS === "1" ||
   process.env.OSGREP_DEBUG_MODELS === "true";
@@ -16,9 +30,23 @@ const log = (...args: unknown[]) => {
   if (LOG_MODELS) console.log(...args);
 };
 
+// The model isn't loaded here, so only the env override is known; without it
+// load() resolves the limit described by maxLengthDefault.
+export function describeConfig() {
+  return {
+    maxLengthOverride:
+      Number.isFinite(ENV_MAX_LENGTH) && ENV_MAX_LENGTH > 0
+        ? Math.min(ENV_MAX_LENGTH, MAX_LENGTH_CEILING)
+        : null,
+    maxLengthDefault: `model_max_length (max ${MAX_LENGTH_CEILING}), or ${DEFAULT_MAX_LENGTH} if unset`,
+    pooling: POOLING,
+  };
+}
+
 export class GraniteModel {
   private session: ort.InferenceSession | null = null;
   private tokenizer: PreTrainedTokenizer | null = null;
+  private maxLength = DEFAULT_MAX_LENGTH;
   private readonly vectorDimensions = CONFIG.VECTOR_DIM;
 
   private resolvePaths(): { modelPath: string; tokenizerPath: string } {
@@ -48,20 +76,37 @@ export class GraniteModel {
 
     this.tokenizer = await AutoTokenizer.from_pretrained(tokenizerPath);
 
-    const sessionOptions: ort.InferenceSession.SessionOptions = {
-      executionProviders: ["cpu"],
-      intraOpNumThreads: ONNX_THREADS,
-      interOpNumThreads: 1,
-      graphOptimizationLevel: "all",
-    };
-    this.session = await ort.InferenceSession.create(modelPath, sessionOptions);
+    // Honor tokenizer_config.json's model_max_length when it is a real limit;
+    // sentinel values keep DEFAULT_MAX_LENGTH.
+    const configured = this.tokenizer.model_max_length;
+    const hasModelLimit =
+      Number.isFinite(configured) &&
+      configured > 0 &&
+      configured <= MAX_LENGTH_SENTINEL;
+    const limit = hasModelLimit
+      ? Math.min(configured, MAX_LENGTH_CEILING)
+      : MAX_LENGTH_CEILING;
+    if (hasModelLimit) this.maxLength = limit;
+    if (Number.isFinite(ENV_MAX_LENGTH) && ENV_MAX_LENGTH > 0) {
+      if (ENV_MAX_LENGTH > limit) {
+        console.warn(
+          `[osgrep] Granite OSGREP_DENSE_MAXLEN ${ENV_MAX_LENGTH} exceeds the model limit; using ${limit}`,
+        );
+      }
+      this.maxLength = Math.min(ENV_MAX_LENGTH, limit);
+    }
+
+    this.session = await createOnnxSession(modelPath, "Granite");
+    log(
+      `Worker: Granite inputs [${this.session.inputNames.join(", ")}], outputs [${this.session.outputNames.join(", ")}]`,
+    );
   }
 
   isReady(): boolean {
     return !!(this.session && this.tokenizer);
   }
 
-  private meanPool(
+  private pool(
     hidden: Float32Array,
     attention: BigInt64Array,
     batch: number,
@@ -72,54 +117,65 @@ export class GraniteModel {
     const vectors: Float32Array[] = [];
     const seqFromMask = attention.length / Math.max(1, batch);
     const usableSeq = Math.min(seq, seqFromMask);
-    const dim = Math.min(hiddenDim, targetDim);
+    // runBatch rejects targetDim > hiddenDim, so this only ever truncates.
+    const dim = targetDim;
 
     for (let b = 0; b < batch; b++) {
-      const sum = new Float32Array(dim);
-      let count = 0;
+      const pooled = new Float32Array(dim);
       const attOffset = b * seqFromMask;
       const hiddenOffset = b * seq * hiddenDim;
 
-      for (let s = 0; s < usableSeq; s++) {
-        if (attention[attOffset + s] > 0) {
-          count++;
-          const tokenOffset = hiddenOffset + s * hiddenDim;
+      if (POOLING === "cls") {
+        pooled.set(hidden.subarray(hiddenOffset, hiddenOffset + dim));
+      } else {
+        if (POOLING === "max") pooled.fill(-Infinity);
+        let count = 0;
+        for (let s = 0; s < usableSeq; s++) {
+          if (attention[attOffset + s] > 0) {
+            count++;
+            const tokenOffset = hiddenOffset + s * hiddenDim;
+            for (let d = 0; d < dim; d++) {
+              const val = hidden[tokenOffset + d];
+              if (POOLING === "max") {
+                if (val > pooled[d]) pooled[d] = val;
+              } else {
+                pooled[d] += val;
+              }
+            }
+          }
+        }
+
+        if (count === 0) {
+          pooled.fill(0);
+        } else if (POOLING === "mean") {
           for (let d = 0; d < dim; d++) {
-            sum[d] += hidden[tokenOffset + d];
+            pooled[d] /= count;
           }
         }
       }
 
-      if (count === 0) count = 1;
       let norm = 0;
       for (let d = 0; d < dim; d++) {
-        sum[d] /= count;
-        norm += sum[d] * sum[d];
+        norm += pooled[d] * pooled[d];
       }
       norm = Math.sqrt(norm) || 1;
       for (let d = 0; d < dim; d++) {
-        sum[d] /= norm;
+        pooled[d] /= norm;
       }
 
-      if (dim < targetDim) {
-        const padded = new Float32Array(targetDim);
-        padded.set(sum);
-        vectors.push(padded);
-      } else {
-        vectors.push(sum);
-      }
+      vectors.push(pooled);
     }
 
     return vectors;
   }
 
   async runBatch(texts: string[]): Promise<Float32Array[]> {
-    if (!this.session || !this.tokenizer) return [];
+    if (!this.session || !this.tokenizer || texts.length === 0) return [];
 
     const encoded = await this.tokenizer(texts, {
       padding: true,
       truncation: true,
-      max_length: 256,
+      max_length: this.maxLength,
     });
 
     type EncodedTensor = { data: BigInt64Array; dims?: number[] };
@@ -141,7 +197,7 @@ export class GraniteModel {
         ? tokenTypeIdsRaw.data
         : new BigInt64Array(inputIds.length).fill(BigInt(0));
 
-    const feeds = {
+    const feeds: Record<string, ort.Tensor> = {
       input_ids: new ort.Tensor("int64", inputIds, [texts.length, seqLen]),
       attention_mask: new ort.Tensor("int64", attentionMask, [
         texts.length,
@@ -153,7 +209,9 @@ export class GraniteModel {
       ]),
     };
 
-    const sessionOut = await this.session.run(feeds);
+    const sessionOut = await this.session.run(
+      withPositionIds(this.session, feeds),
+    );
     const hidden =
       sessionOut.last_hidden_state ?? sessionOut[this.session.outputNames[0]];
 
@@ -163,7 +221,17 @@ export class GraniteModel {
 
     const hiddenData = hidden.data as Float32Array;
     const [batch, seq, dim] = hidden.dims as number[];
-    return this.meanPool(
+    if (hidden.dims.length !== 3 || batch !== texts.length || seq !== seqLen) {
+      throw new Error(
+        `Granite ONNX output shape mismatch: got [${hidden.dims.join(", ")}], expected [${texts.length}, ${seqLen}, hidden]`,
+      );
+    }
+    if (this.vectorDimensions > dim) {
+      throw new Error(
+        `OSGREP_VECTOR_DIM ${this.vectorDimensions} exceeds the Granite hidden size ${dim}`,
+      );
+    }
+    return this.pool(
       hiddenData,
       attentionMask,
       batch,
@@ -0,0 +1,88 @@
+import * as ort from "onnxruntime-node";
+import { CONFIG } from "../../config";
+
+// "cpu" (default), "cuda" or "cuda:<device id>"
+const ONNX_DEVICE = (process.env.OSGREP_ONNX_DEVICE ?? "cpu")
+  .trim()
+  .toLowerCase();
+
+type ExecutionProviders =
+  ort.InferenceSession.SessionOptions["executionProviders"];
+
+let warnedUnknownDevice = false;
+
+function executionProviders(): ExecutionProviders {
+  const match = /^cuda(?::(\d+))?$/.exec(ONNX_DEVICE);
+  if (!match) {
+    if (ONNX_DEVICE !== "cpu" && !warnedUnknownDevice) {
+      warnedUnknownDevice = true;
+      console.warn(
+        `[osgrep] Unknown OSGREP_ONNX_DEVICE "${ONNX_DEVICE}" (expected cpu, cuda or cuda:<id>); using CPU`,
+      );
+    }
+    return ["cpu"];
+  }
+  const deviceId = match[1] ? Number.parseInt(match[1], 10) : 0;
+  return [{ name: "cuda", deviceId }, "cpu"];
+}
+
+// Requested device and the provider list sessions will try, in order.
+export function describeConfig() {
+  return { device: ONNX_DEVICE, executionProviders: executionProviders() };
+}
+
+/**
+ * Create an ONNX session with the configured threads and device.
+ * If the GPU provider can't be registered, log it and fall back to CPU.
+ */
+export async function createOnnxSession(
+  modelPath: string,
+  label: string,
+): Promise<ort.InferenceSession> {
+  const base: ort.InferenceSession.SessionOptions = {
+    intraOpNumThreads: CONFIG.ONNX_THREADS,
+    interOpNumThreads: CONFIG.ONNX_THREADS,
+    graphOptimizationLevel: "all",
+  };
+  const providers = executionProviders();
+
+  try {
+    return await ort.InferenceSession.create(modelPath, {
+      ...base,
+      executionProviders: providers,
+    });
+  } catch (err) {
+    if (providers?.length === 1) throw err;
+    console.warn(
+      `[osgrep] ${label}: ${ONNX_DEVICE} execution provider unavailable, using CPU (${
+        err instanceof Error ? err.message : String(err)
+      })`,
+    );
+    return ort.InferenceSession.create(modelPath, {
+      ...base,
+      executionProviders: ["cpu"],
+    });
+  }
+}
+
+/**
+ * Some exports take an explicit position_ids input; supply 0..seq per row
+ * when the session declares it and the caller didn't.
+ */
+export function withPositionIds(
+  session: ort.InferenceSession,
+  feeds: Record<string, ort.Tensor>,
+): Record<string, ort.Tensor> {
+  if (feeds.position_ids || !session.inputNames.includes("position_ids")) {
+    return feeds;
+  }
+  const [batch, seq] = feeds.input_ids.dims as number[];
+  const positionIds = new BigInt64Array(batch * seq);
+  for (let i = 0; i < positionIds.length; i++) {
+    positionIds[i] = BigInt(i % seq);
+  }
+  return {
+    ...feeds,
+    position_ids: new ort.Tensor("int64", positionIds, [batch, seq]),
+  };
+}
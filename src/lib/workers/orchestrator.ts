@@ -3,7 +3,7 @@ import * as path from "node:path";
 import { env } from "@huggingface/transformers";
 import * as ort from "onnxruntime-node";
 import { v4 as uuidv4 } from "uuid";
-import { CONFIG, PATHS, PROVIDERS } from "../../config";
+import { CONFIG, HF_ENDPOINT, PATHS, PROVIDERS } from "../../config";
 import {
   buildAnchorChunk,
   type ChunkWithContext,
@@ -18,10 +18,15 @@ import {
   isIndexableFile,
   readFileSnapshot,
 } from "../utils/file-utils";
-import { maxSim } from "./colbert-math";
+import { maxSim, skipListSize } from "./colbert-math";
+import { describeConfig as describeTokenizer } from "./colbert-tokenizer";
 import { ColbertModel, type HybridResult } from "./embeddings/colbert";
-import { GraniteModel } from "./embeddings/granite";
+import {
+  describeConfig as describeGranite,
+  GraniteModel,
+} from "./embeddings/granite";
 import { QwenModel } from "./embeddings/qwen";
+import { describeConfig as describeOnnx } from "./onnx-session";
 import {
   zerankRerank,
   type RerankWithTextInput,
@@ -70,9 +75,37 @@ const log = (...args: unknown[]) => {
   if (LOG_MODELS) console.log(...args);
 };
 
+// Exclude [CLS]/[SEP]/marker doc positions from MaxSim even if the skiplist omits them
+const SKIP_SPECIAL_TOKENS =
+  process.env.OSGREP_COLBERT_SKIP_SPECIAL === "1" ||
+  process.env.OSGREP_COLBERT_SKIP_SPECIAL === "true";
+// Local embedding batch size; bounds peak ONNX memory per worker.
+const envBatch = Number.parseInt(
+  process.env.OSGREP_WORKER_BATCH_SIZE ?? "",
+  10,
+);
+const BATCH_SIZE =
+  Number.isFinite(envBatch) && envBatch > 0 ? Math.min(256, envBatch) : 16;
+
+// Resolved worker-side settings for `osgrep doctor --config`.
+export function describeConfig() {
+  return {
+    hfEndpoint: HF_ENDPOINT,
+    workerBatchSize: BATCH_SIZE,
+    onnx: describeOnnx(),
+    dense: describeGranite(),
+    colbert: {
+      ...describeTokenizer(),
+      skipSpecialTokens: SKIP_SPECIAL_TOKENS,
+      skipListSize: skipListSize(),
+    },
+  };
+}
+
 env.cacheDir = CACHE_DIR;
 env.allowLocalModels = true;
 env.allowRemoteModels = true;
+env.remoteHost = `${HF_ENDPOINT}/`;
 
 const PROJECT_ROOT = process.env.OSGREP_PROJECT_ROOT
   ? path.resolve(process.env.OSGREP_PROJECT_ROOT)
@@ -143,19 +176,13 @@ export class WorkerOrchestrator {
 
     // For local providers, batch to avoid memory issues with ONNX
     const results: HybridResult[] = [];
-    const envBatch = Number.parseInt(
-      process.env.OSGREP_WORKER_BATCH_SIZE ?? "",
-      10,
-    );
-    const BATCH_SIZE =
-      Number.isFinite(envBatch) && envBatch > 0
-        ? Math.max(4, Math.min(16, envBatch))
-        : 16;
 
     for (let i = 0; i < texts.length; i += BATCH_SIZE) {
       if (i > 0) onProgress?.();
       const batchTexts = texts.slice(i, i + BATCH_SIZE);
-      const denseBatch = await this.embedModel.runBatch(batchTexts);
+      const denseBatch = await this.embedModel.runBatch(
+        batchTexts.map((t) => `${CONFIG.DOC_PREFIX}${t}`),
+      );
 
       // Local: compute ColBERT embeddings for reranking
       const colbertBatch = await this.colbert.runBatch(
@@ -331,7 +358,18 @@ export class WorkerOrchestrator {
   }> {
     await this.ensureReady();
 
-    const [denseVector] = await this.embedModel.runBatch([text]);
+    const template = CONFIG.QUERY_TEMPLATE;
+    const queryText = !template
+      ? text
+      : template.includes("{query}")
+        ? template.replace("{query}", () => text)
+        : `${template}${text}`;
+
+    // Dense prefixes target the local model; cloud embeddings get raw text.
+    const queryPrefix = this.useLocalColbert ? CONFIG.QUERY_PREFIX : "";
+    const [denseVector] = await this.embedModel.runBatch([
+      `${queryPrefix}${queryText}`,
+    ]);
 
     // For cloud providers, we don't have ColBERT - return empty matrix
     if (!this.useLocalColbert) {
@@ -343,7 +381,7 @@ export class WorkerOrchestrator {
       };
     }
 
-    const encoded = await this.colbert.encodeQuery(text);
+    const encoded = await this.colbert.encodeQuery(queryText);
 
     const feeds = {
       input_ids: new ort.Tensor("int64", encoded.input_ids, [
@@ -427,6 +465,10 @@ export class WorkerOrchestrator {
     const queryMatrix = input.query.map((row) =>
       row instanceof Float32Array ? row : new Float32Array(row),
     );
+    const specialIds =
+      SKIP_SPECIAL_TOKENS && this.colbert.tokenizer
+        ? new Set(this.colbert.tokenizer.getSpecialTokenIds())
+        : undefined;
 
     return input.docs.map((doc) => {
       const col = doc.colbert;
@@ -451,7 +493,23 @@ export class WorkerOrchestrator {
         colbert = new Int8Array(0);
       }
 
-      const seqLen = Math.floor(colbert.length / input.colbertDim);
+      // One row per stored token id; a wider model's rows can still be a
+      // multiple of colbertDim, so the modulo check is only a fallback.
+      const tokenCount = Array.isArray(doc.token_ids)
+        ? doc.token_ids.length
+        : 0;
+      const widthMismatch =
+        tokenCount > 0
+          ? tokenCount * input.colbertDim !== colbert.length
+          : colbert.length % input.colbertDim !== 0;
+      if (widthMismatch) {
+        throw new Error(
+          `ColBERT embedding length ${colbert.length} does not match the model dimension ${input.colbertDim}${
+            tokenCount > 0 ? ` for ${tokenCount} tokens` : ""
+          }; the index was likely built with a different model. Re-run "osgrep index --reset".`,
+        );
+      }
+      const seqLen = colbert.length / input.colbertDim;
       const docMatrix: Float32Array[] = [];
       for (let i = 0; i < seqLen; i++) {
         const start = i * input.colbertDim;
@@ -465,7 +523,7 @@ export class WorkerOrchestrator {
         Array.isArray(doc.token_ids) && doc.token_ids.length === seqLen
           ? doc.token_ids
           : undefined;
-      return maxSim(queryMatrix, docMatrix, tokenIds);
+      return maxSim(queryMatrix, docMatrix, tokenIds, specialIds);
     });
   }
 
@@ -0,0 +1,92 @@
+import { describe, expect, it, vi } from "vitest";
+
+vi.mock("onnxruntime-node", () => ({
+  Tensor: class {
+    constructor(
+      public type: string,
+      public data: BigInt64Array,
+      public dims: number[],
+    ) {}
+  },
+  InferenceSession: { create: vi.fn() },
+}));
+
+// Whitespace tokenizer whose [PAD] resolves to a non-default id.
+const PAD_ID = 7;
+vi.mock("@huggingface/transformers", () => {
+  const vocab = new Map<string, number>([
+    ["[CLS]", 1],
+    ["[SEP]", 2],
+    ["[PAD]", PAD_ID],
+    ["[MASK]", 4],
+    ["[Q] ", 5],
+    ["[D] ", 6],
+  ]);
+  const tokenizer = Object.assign(
+    async (text: string) => ({
+      input_ids: {
+        data: BigInt64Array.from(
+          text.split(/\s+/).filter(Boolean),
+          (_w, i) => BigInt(100 + i),
+        ),
+      },
+    }),
+    {
+      model: { tokens_to_ids: vocab },
+      cls_token: "[CLS]",
+      sep_token: "[SEP]",
+      pad_token: "[PAD]",
+      model_max_length: 512,
+    },
+  );
+  return {
+    AutoTokenizer: { from_pretrained: vi.fn(async () => tokenizer) },
+  };
+});
+
+import { ColBERTTokenizer } from "../src/lib/workers/colbert-tokenizer";
+import { ColbertModel } from "../src/lib/workers/embeddings/colbert";
+
+describe("ColbertModel.runBatch padding", () => {
+  it("pads shorter docs with the tokenizer's resolved pad id", async () => {
+    const tokenizer = new ColBERTTokenizer();
+    await tokenizer.init("/models/fake");
+    expect(tokenizer.getPadId()).toBe(PAD_ID);
+
+    const dim = 4;
+    let captured: Record<string, { data: BigInt64Array; dims: number[] }> =
+      {};
+    const session = {
+      inputNames: ["input_ids", "attention_mask"],
+      outputNames: ["out"],
+      run: vi.fn(async (feeds: typeof captured) => {
+        captured = feeds;
+        const [batch, seq] = feeds.input_ids.dims;
+        return {
+          out: {
+            data: new Float32Array(batch * seq * dim).fill(1),
+            dims: [batch, seq, dim],
+          },
+        };
+      }),
+    };
+
+    const model = new ColbertModel();
+    const internals = model as unknown as {
+      session: typeof session;
+      tokenizer: ColBERTTokenizer;
+    };
+    internals.session = session;
+    internals.tokenizer = tokenizer;
+
+    // "a" -> [CLS] [D] a [SEP] (4), "a b c" -> 6 tokens
+    await model.runBatch(["a", "a b c"], [], dim);
+
+    const ids = Array.from(captured.input_ids.data, Number);
+    const mask = Array.from(captured.attention_mask.data, Number);
+    expect(captured.input_ids.dims).toEqual([2, 6]);
+    expect(ids.slice(4, 6)).toEqual([PAD_ID, PAD_ID]);
+    expect(mask.slice(0, 6)).toEqual([1, 1, 1, 1, 0, 0]);
+    expect(ids.slice(6)).not.toContain(PAD_ID);
+  });
+});
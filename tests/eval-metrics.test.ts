@@ -0,0 +1,43 @@
+import { describe, expect, it, vi } from "vitest";
+
+vi.mock("../src/lib/search/searcher", () => ({ Searcher: vi.fn() }));
+vi.mock("../src/lib/store/vector-db", () => ({ VectorDB: vi.fn() }));
+
+import { ndcgAtK, recallAtK } from "../src/eval";
+
+describe("recallAtK", () => {
+  it("returns 0 when nothing is relevant", () => {
+    expect(recallAtK([0, 1, 2], [], 10)).toBe(0);
+  });
+
+  it("ignores hits ranked below k", () => {
+    expect(recallAtK([5, 6, 7, 1], [1], 3)).toBe(0);
+    expect(recallAtK([5, 6, 7, 1], [1], 4)).toBe(1);
+  });
+
+  it("counts the fraction of relevant docs in the top k", () => {
+    expect(recallAtK([1, 9, 2], [1, 2, 3, 4], 3)).toBe(0.5);
+  });
+});
+
+describe("ndcgAtK", () => {
+  it("returns 0 when the ideal ranking has no gain", () => {
+    expect(ndcgAtK([0, 1, 2], [0, 0, 0], 10)).toBe(0);
+    expect(ndcgAtK([0, 1, 2], [], 10)).toBe(0);
+  });
+
+  it("returns 0 when the only hit ranks below k", () => {
+    expect(ndcgAtK([0, 1, 2], [0, 0, 1], 2)).toBe(0);
+  });
+
+  it("is 1 for the ideal ordering", () => {
+    expect(ndcgAtK([2, 0, 1], [1, 0, 3], 3)).toBeCloseTo(1);
+  });
+
+  it("weights graded relevance by position", () => {
+    // relevance: doc0=1, doc1=0, doc2=3
+    const ideal = 7 + 1 / Math.log2(3);
+    const swapped = 1 + 7 / Math.log2(3);
+    expect(ndcgAtK([0, 2, 1], [1, 0, 3], 3)).toBeCloseTo(swapped / ideal);
+  });
+});
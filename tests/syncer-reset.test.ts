@@ -0,0 +1,92 @@
+import * as fs from "node:fs";
+import * as os from "node:os";
+import * as path from "node:path";
+import { describe, expect, it, vi } from "vitest";
+
+const calls: string[] = [];
+
+// An index built for another vector width: every open fails validation
+// until the table is dropped.
+vi.mock("../src/lib/store/vector-db", () => ({
+  VectorDB: class {
+    private stale = true;
+    async hasAnyRows() {
+      calls.push("hasAnyRows");
+      if (this.stale) {
+        throw new Error(
+          '[vector-db] index vectors have 4096 dimensions but 384 are configured. Please run "osgrep index --reset" to rebuild the index.',
+        );
+      }
+      return false;
+    }
+    async drop() {
+      calls.push("drop");
+      this.stale = false;
+    }
+    async insertBatch() {}
+    async deletePaths() {}
+    async createFTSIndex() {}
+    async close() {}
+  },
+}));
+
+vi.mock("../src/lib/store/meta-cache", () => ({
+  MetaCache: class {
+    get() {
+      return undefined;
+    }
+    async getAllKeys() {
+      return new Set<string>();
+    }
+    put() {}
+    delete() {}
+    close() {}
+  },
+}));
+
+vi.mock("../src/lib/utils/lock", () => ({
+  acquireWriterLockWithRetry: vi.fn(async () => ({
+    release: vi.fn(async () => {}),
+  })),
+}));
+
+vi.mock("../src/lib/index/walker", () => ({
+  walk: async function* () {},
+}));
+
+vi.mock("../src/lib/utils/project-root", () => ({
+  ensureProjectPaths: (root: string) => ({
+    root,
+    osgrepDir: path.join(root, ".osgrep"),
+    lancedbDir: path.join(root, ".osgrep", "lancedb"),
+    lmdbPath: path.join(root, ".osgrep", "cache.lmdb"),
+  }),
+}));
+
+import { initialSync } from "../src/lib/index/syncer";
+
+describe("initialSync reset", () => {
+  it("rebuilds an index whose vector width no longer matches", async () => {
+    const root = fs.mkdtempSync(path.join(os.tmpdir(), "osgrep-reset-"));
+    try {
+      const result = await initialSync({ projectRoot: root, reset: true });
+
+      expect(result.failedFiles).toBe(0);
+      expect(calls[0]).toBe("drop");
+      expect(calls).toContain("hasAnyRows");
+    } finally {
+      fs.rmSync(root, { recursive: true, force: true });
+    }
+  });
+
+  it("still surfaces the mismatch without reset", async () => {
+    const root = fs.mkdtempSync(path.join(os.tmpdir(), "osgrep-reset-"));
+    try {
+      await expect(initialSync({ projectRoot: root })).rejects.toThrow(
+        /osgrep index --reset/,
+      );
+    } finally {
+      fs.rmSync(root, { recursive: true, force: true });
+    }
+  });
+});